@@ -0,0 +1,150 @@
+//! Transparent decompression for `rat`'s file inputs. A path is sniffed by
+//! extension and, failing that, by leading magic bytes, and wrapped in the
+//! matching streaming decoder so the rest of the flag pipeline just sees
+//! plain bytes.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom},
+};
+
+use bzip2::read::BzDecoder;
+use flate2::read::GzDecoder;
+use xz2::read::XzDecoder;
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zip,
+}
+
+fn compression_from_extension(path: &str) -> Option<Compression> {
+    let lower = path.to_lowercase();
+
+    if lower.ends_with(".gz") {
+        Some(Compression::Gzip)
+    } else if lower.ends_with(".bz2") {
+        Some(Compression::Bzip2)
+    } else if lower.ends_with(".xz") {
+        Some(Compression::Xz)
+    } else if lower.ends_with(".zip") {
+        Some(Compression::Zip)
+    } else {
+        None
+    }
+}
+
+fn compression_from_magic(magic: &[u8]) -> Compression {
+    if magic.starts_with(&[0x1f, 0x8b]) {
+        Compression::Gzip
+    } else if magic.starts_with(&[0x42, 0x5a, 0x68]) {
+        Compression::Bzip2
+    } else if magic.starts_with(&[0xfd, 0x37, 0x7a, 0x58, 0x5a]) {
+        Compression::Xz
+    } else if magic.starts_with(&[0x50, 0x4b, 0x03, 0x04]) {
+        Compression::Zip
+    } else {
+        Compression::None
+    }
+}
+
+/// Peeks the first few bytes of `file` without disturbing its read position.
+fn sniff_magic(file: &mut File) -> io::Result<[u8; 5]> {
+    let mut magic = [0u8; 5];
+    let read = file.read(&mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(if read == magic.len() {
+        magic
+    } else {
+        let mut truncated = [0u8; 5];
+        truncated[..read].copy_from_slice(&magic[..read]);
+        truncated
+    })
+}
+
+/// Concatenates every entry of a zip archive into one buffer, the way
+/// piping each entry through `cat` in sequence would.
+fn read_zip_entries(file: File) -> io::Result<Vec<u8>> {
+    let mut archive =
+        ZipArchive::new(file).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let mut contents = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        io::copy(&mut entry, &mut contents)?;
+    }
+
+    Ok(contents)
+}
+
+/// Opens `path` and, if it looks like a gzip/bzip2/xz/zip archive, wraps it
+/// in the matching streaming decoder. Falls back to a plain `BufReader`
+/// when no compression is detected.
+pub fn open_reader(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let mut file = File::open(path)?;
+
+    let compression = match compression_from_extension(path) {
+        Some(compression) => compression,
+        None => compression_from_magic(&sniff_magic(&mut file)?),
+    };
+
+    match compression {
+        Compression::None => Ok(Box::new(BufReader::new(file))),
+        Compression::Gzip => Ok(Box::new(BufReader::new(GzDecoder::new(file)))),
+        Compression::Bzip2 => Ok(Box::new(BufReader::new(BzDecoder::new(file)))),
+        Compression::Xz => Ok(Box::new(BufReader::new(XzDecoder::new(file)))),
+        Compression::Zip => Ok(Box::new(BufReader::new(Cursor::new(read_zip_entries(
+            file,
+        )?)))),
+    }
+}
+
+#[cfg(test)]
+mod compression_detection_tests {
+    use super::{compression_from_extension, compression_from_magic, Compression};
+
+    #[test]
+    fn detects_compression_by_extension_case_insensitively() {
+        assert_eq!(compression_from_extension("a.GZ"), Some(Compression::Gzip));
+        assert_eq!(
+            compression_from_extension("a.bz2"),
+            Some(Compression::Bzip2)
+        );
+        assert_eq!(compression_from_extension("a.Xz"), Some(Compression::Xz));
+        assert_eq!(compression_from_extension("a.zip"), Some(Compression::Zip));
+    }
+
+    #[test]
+    fn returns_none_for_an_unrecognized_extension() {
+        assert_eq!(compression_from_extension("a.txt"), None);
+    }
+
+    #[test]
+    fn detects_compression_by_magic_bytes() {
+        assert_eq!(
+            compression_from_magic(&[0x1f, 0x8b, 0x08]),
+            Compression::Gzip
+        );
+        assert_eq!(compression_from_magic(b"BZh9"), Compression::Bzip2);
+        assert_eq!(
+            compression_from_magic(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]),
+            Compression::Xz
+        );
+        assert_eq!(
+            compression_from_magic(&[0x50, 0x4b, 0x03, 0x04]),
+            Compression::Zip
+        );
+    }
+
+    #[test]
+    fn falls_back_to_none_for_unrecognized_or_short_magic() {
+        assert_eq!(compression_from_magic(b"plain"), Compression::None);
+        assert_eq!(compression_from_magic(&[]), Compression::None);
+    }
+}
@@ -1,8 +1,15 @@
-use std::{env, fs, io, process::exit};
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process::exit,
+};
+
+mod decompress;
 
 #[derive(Debug)]
 enum RatErrorType {
     InvalidFlag,
+    AmbiguousFlag,
     NoFileFound,
 }
 
@@ -10,11 +17,28 @@ enum RatErrorType {
 struct RatError {
     error: RatErrorType,
     message: String,
+    suggestion: Option<String>,
 }
 
 impl RatError {
     fn new(error: RatErrorType, message: String) -> RatError {
-        RatError { error, message }
+        RatError {
+            error,
+            message,
+            suggestion: None,
+        }
+    }
+
+    fn with_suggestion(
+        error: RatErrorType,
+        message: String,
+        suggestion: Option<String>,
+    ) -> RatError {
+        RatError {
+            error,
+            message,
+            suggestion,
+        }
     }
 }
 
@@ -52,6 +76,20 @@ impl RatArgs {
     }
 }
 
+/// Long option names `parse` understands, used both for exact matching and
+/// for resolving unambiguous prefixes (e.g. `--number-non`).
+const LONG_FLAGS: &[&str] = &[
+    "number",
+    "squeeze-blank",
+    "number-nonblank",
+    "show-tabs",
+    "show-ends",
+    "show-nonprinting",
+    "show-all",
+    "help",
+    "version",
+];
+
 impl RatArgs {
     fn parse(args: env::Args) -> RatArgs {
         let mut r = RatArgs::new();
@@ -61,23 +99,40 @@ impl RatArgs {
         for arg in &args_vec[1..] {
             if arg.eq("-") {
                 r.paths.push(arg.to_string())
-            } else if arg.starts_with("-") || arg.starts_with("--") {
-                let flag = arg.trim_start_matches("-");
-                match flag {
-                    "n" | "number" => r.flags.output_nums = true,
-                    "s" | "squeeze-blank" => r.flags.squeeze_blank = true,
-                    "b" | "number-nonblank" => r.flags.number_nonblank = true,
-                    "T" | "show-tabs" => r.flags.show_tabs = true,
-                    "E" | "show-ends" => r.flags.show_ends = true,
-                    "v" | "show-nonprinting" => r.flags.show_nonprinting = true,
-                    "h" | "help" => display_help(),
-                    "version" => display_version(),
-                    default => {
-                        r.error = Some(RatError::new(
+            } else if let Some(long_flag) = arg.strip_prefix("--") {
+                match resolve_long_flag(long_flag) {
+                    Ok(name) => apply_flag(&mut r, name),
+                    Err(LongFlagError::Unknown) => {
+                        let normalized = long_flag.trim_start_matches('-').to_lowercase();
+                        r.error = Some(RatError::with_suggestion(
                             RatErrorType::InvalidFlag,
-                            format!("Invalid flag '{}'", default),
+                            format!("Invalid flag '{}'", long_flag),
+                            suggest_flag(&normalized),
                         ))
                     }
+                    Err(LongFlagError::Ambiguous(candidates)) => {
+                        r.error = Some(RatError::new(
+                            RatErrorType::AmbiguousFlag,
+                            format!(
+                                "Ambiguous option '--{}' ({})",
+                                long_flag,
+                                candidates.join(", ")
+                            ),
+                        ))
+                    }
+                }
+            } else if let Some(bundle) = arg.strip_prefix("-") {
+                for c in bundle.chars() {
+                    match apply_short_flag(&mut r, c) {
+                        Ok(()) => {}
+                        Err(()) => {
+                            r.error = Some(RatError::with_suggestion(
+                                RatErrorType::InvalidFlag,
+                                format!("Invalid flag '{}'", c),
+                                suggest_flag(&c.to_string()),
+                            ))
+                        }
+                    }
                 }
             } else {
                 r.paths.push(arg.to_string());
@@ -88,94 +143,521 @@ impl RatArgs {
     }
 }
 
-fn run(args: RatArgs) {
-    let mut concatenated_files = String::new();
-    for path in args.paths {
-        if path.eq("-") {
-            print_concatenated_files(concatenated_files.clone(), args.flags);
-            enter_repl();
-        } else {
-            match fs::read_to_string(path) {
-                Ok(data) => concatenated_files.push_str(data.as_str()),
-                Err(err) => {
-                    handle_error(RatError::new(RatErrorType::NoFileFound, format!("{}", err)))
-                }
+/// Why a `--long-flag` token failed to resolve, so callers can tell a typo
+/// (no candidates) apart from a prefix that fits more than one flag.
+#[derive(Debug)]
+enum LongFlagError {
+    Unknown,
+    Ambiguous(Vec<&'static str>),
+}
+
+/// Resolves a `--long-flag` token to one of `LONG_FLAGS`, accepting any
+/// unambiguous prefix (`--number-non` -> `number-nonblank`) the way tolerant
+/// GNU-style CLIs do. Case and repeated leading dashes are normalized first.
+fn resolve_long_flag(token: &str) -> Result<&'static str, LongFlagError> {
+    let normalized = token.trim_start_matches('-').to_lowercase();
+
+    if let Some(exact) = LONG_FLAGS.iter().find(|name| **name == normalized) {
+        return Ok(exact);
+    }
+
+    if normalized.is_empty() {
+        return Err(LongFlagError::Unknown);
+    }
+
+    let candidates: Vec<&'static str> = LONG_FLAGS
+        .iter()
+        .copied()
+        .filter(|name| name.starts_with(normalized.as_str()))
+        .collect();
+
+    match candidates.len() {
+        0 => Err(LongFlagError::Unknown),
+        1 => Ok(candidates[0]),
+        _ => Err(LongFlagError::Ambiguous(candidates)),
+    }
+}
+
+#[cfg(test)]
+mod resolve_long_flag_tests {
+    use super::{resolve_long_flag, LongFlagError};
+
+    #[test]
+    fn resolves_an_exact_match() {
+        assert_eq!(resolve_long_flag("number").unwrap(), "number");
+    }
+
+    #[test]
+    fn resolves_an_unambiguous_prefix() {
+        assert_eq!(resolve_long_flag("number-non").unwrap(), "number-nonblank");
+    }
+
+    #[test]
+    fn is_case_insensitive() {
+        assert_eq!(resolve_long_flag("NUMBER").unwrap(), "number");
+    }
+
+    #[test]
+    fn rejects_an_ambiguous_prefix() {
+        match resolve_long_flag("s") {
+            Err(LongFlagError::Ambiguous(candidates)) => {
+                assert!(candidates.contains(&"squeeze-blank"));
+                assert!(candidates.contains(&"show-tabs"));
             }
+            _ => panic!("expected an Ambiguous error"),
         }
     }
 
-    print_concatenated_files(concatenated_files, args.flags)
+    #[test]
+    fn rejects_an_unknown_flag() {
+        assert!(matches!(
+            resolve_long_flag("not-a-flag"),
+            Err(LongFlagError::Unknown)
+        ));
+    }
+
+    #[test]
+    fn rejects_an_empty_token_as_unknown_rather_than_ambiguous() {
+        assert!(matches!(resolve_long_flag(""), Err(LongFlagError::Unknown)));
+        assert!(matches!(
+            resolve_long_flag("--"),
+            Err(LongFlagError::Unknown)
+        ));
+    }
 }
 
-fn print_concatenated_files(data: String, flags: RatFlags) {
-    let mut line_count = 1;
-    let mut previous_line_empty = false;
+/// Known long flag names offered as "did you mean" suggestions.
+const SUGGESTION_FLAGS: &[&str] = &[
+    "number",
+    "squeeze-blank",
+    "number-nonblank",
+    "show-tabs",
+    "show-ends",
+    "show-nonprinting",
+    "help",
+    "version",
+];
 
-    for line in data.lines() {
-        let mut line_to_print = line.to_string();
+/// Levenshtein edit distance between `a` and `b`, computed with a rolling
+/// two-row DP so we don't allocate an `m x n` matrix.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let n = b_chars.len();
 
-        if flags.show_nonprinting {
-            line_to_print.clear();
-            for ch in line.chars() {
-                if (ch as u32) <= 31 {
-                    match char::from_u32((ch as u32) + 64) {
-                        Some(c) => line_to_print.push_str(format!("^{}", c).as_str()),
-                        None => continue,
-                    }
-                } else {
-                    line_to_print.push(ch)
-                }
-            }
+    let mut prev: Vec<usize> = (0..=n).collect();
+    let mut cur = vec![0; n + 1];
+
+    for (i, ac) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, &bc) in b_chars.iter().enumerate() {
+            let cost = if ac != bc { 1 } else { 0 };
+            cur[j + 1] = (prev[j + 1] + 1).min(cur[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    prev[n]
+}
+
+#[cfg(test)]
+mod levenshtein_tests {
+    use super::levenshtein;
+
+    #[test]
+    fn identical_strings_have_zero_distance() {
+        assert_eq!(levenshtein("number", "number"), 0);
+    }
+
+    #[test]
+    fn empty_string_costs_the_length_of_the_other() {
+        assert_eq!(levenshtein("", "number"), 6);
+        assert_eq!(levenshtein("number", ""), 6);
+    }
+
+    #[test]
+    fn counts_a_single_substitution() {
+        assert_eq!(levenshtein("numbr", "numbe"), 1);
+    }
+
+    #[test]
+    fn counts_a_single_insertion_or_deletion() {
+        assert_eq!(levenshtein("numbr", "number"), 1);
+        assert_eq!(levenshtein("number", "numbr"), 1);
+    }
+
+    #[test]
+    fn counts_mixed_edits() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn is_symmetric() {
+        assert_eq!(
+            levenshtein("show-tabs", "show-ends"),
+            levenshtein("show-ends", "show-tabs")
+        );
+    }
+}
+
+/// Finds the closest known long flag to `name`, if it's close enough to be
+/// a plausible typo rather than a completely different word.
+fn suggest_flag(name: &str) -> Option<String> {
+    let (closest, distance) = SUGGESTION_FLAGS
+        .iter()
+        .map(|candidate| (*candidate, levenshtein(name, candidate)))
+        .min_by_key(|(_, distance)| *distance)?;
+
+    if distance <= 2 || distance <= name.len() / 2 {
+        Some(closest.to_string())
+    } else {
+        None
+    }
+}
+
+fn apply_flag(r: &mut RatArgs, name: &str) {
+    match name {
+        "number" => r.flags.output_nums = true,
+        "squeeze-blank" => r.flags.squeeze_blank = true,
+        "number-nonblank" => r.flags.number_nonblank = true,
+        "show-tabs" => r.flags.show_tabs = true,
+        "show-ends" => r.flags.show_ends = true,
+        "show-nonprinting" => r.flags.show_nonprinting = true,
+        "show-all" => {
+            r.flags.show_nonprinting = true;
+            r.flags.show_ends = true;
+            r.flags.show_tabs = true;
         }
+        "help" => display_help(),
+        "version" => display_version(),
+        _ => unreachable!("resolve_long_flag only returns names from LONG_FLAGS"),
+    }
+}
 
-        if flags.show_tabs && line.contains("\t") {
-            line_to_print = line_to_print.replace("\t", "^I");
+fn apply_short_flag(r: &mut RatArgs, c: char) -> Result<(), ()> {
+    match c {
+        'n' => r.flags.output_nums = true,
+        's' => r.flags.squeeze_blank = true,
+        'b' => r.flags.number_nonblank = true,
+        'T' => r.flags.show_tabs = true,
+        'E' => r.flags.show_ends = true,
+        'v' => r.flags.show_nonprinting = true,
+        'A' => {
+            r.flags.show_nonprinting = true;
+            r.flags.show_ends = true;
+            r.flags.show_tabs = true;
         }
+        'e' => {
+            r.flags.show_nonprinting = true;
+            r.flags.show_ends = true;
+        }
+        't' => {
+            r.flags.show_nonprinting = true;
+            r.flags.show_tabs = true;
+        }
+        'h' => display_help(),
+        _ => return Err(()),
+    }
+
+    Ok(())
+}
 
-        if flags.show_ends {
-            line_to_print.push('$');
+#[cfg(test)]
+mod apply_short_flag_tests {
+    use super::{apply_short_flag, RatArgs};
+
+    #[test]
+    fn a_bundle_like_bne_sets_each_flag_it_contains() {
+        let mut r = RatArgs::new();
+
+        for c in "bnE".chars() {
+            apply_short_flag(&mut r, c).unwrap();
         }
 
-        if flags.squeeze_blank {
-            if line_to_print.is_empty() && previous_line_empty {
-                continue;
-            }
-            previous_line_empty = line_to_print.is_empty();
+        assert!(r.flags.number_nonblank);
+        assert!(r.flags.output_nums);
+        assert!(r.flags.show_ends);
+        assert!(!r.flags.show_tabs);
+    }
+
+    #[test]
+    fn rejects_an_unknown_short_flag() {
+        let mut r = RatArgs::new();
+        assert_eq!(apply_short_flag(&mut r, 'z'), Err(()));
+    }
+
+    #[test]
+    fn t_is_a_shorthand_for_show_nonprinting_and_show_tabs() {
+        let mut r = RatArgs::new();
+        apply_short_flag(&mut r, 't').unwrap();
+
+        assert!(r.flags.show_nonprinting);
+        assert!(r.flags.show_tabs);
+        assert!(!r.flags.show_ends);
+    }
+}
+
+/// Numbering and squeeze-blank state that threads across files (and stdin)
+/// so a run of `rat a.txt b.txt` numbers lines continuously.
+struct StreamState {
+    line_count: usize,
+    previous_line_empty: bool,
+}
+
+impl StreamState {
+    fn new() -> StreamState {
+        StreamState {
+            line_count: 1,
+            previous_line_empty: false,
         }
+    }
+}
+
+/// Defaults to reading stdin when no files were given, the way `cat` does,
+/// instead of silently producing no output.
+fn effective_paths(paths: Vec<String>) -> Vec<String> {
+    if paths.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        paths
+    }
+}
+
+#[cfg(test)]
+mod effective_paths_tests {
+    use super::effective_paths;
+
+    #[test]
+    fn defaults_to_stdin_when_no_paths_are_given() {
+        assert_eq!(effective_paths(vec![]), vec!["-".to_string()]);
+    }
 
-        if flags.output_nums && !flags.number_nonblank {
-            println!("{}    {}", line_count, line_to_print);
-            line_count += 1;
-        } else if !line.is_empty() && flags.number_nonblank {
-            println!("{}    {}", line_count, line_to_print);
-            line_count += 1;
+    #[test]
+    fn leaves_explicit_paths_untouched() {
+        let paths = vec!["a.txt".to_string(), "-".to_string(), "b.txt".to_string()];
+        assert_eq!(effective_paths(paths.clone()), paths);
+    }
+}
+
+fn run(args: RatArgs) {
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+    let mut state = StreamState::new();
+
+    let paths = effective_paths(args.paths);
+
+    for path in paths {
+        if path.eq("-") {
+            let stdin = io::stdin();
+            let reader = stdin.lock();
+            if let Err(err) = print_concatenated_files(reader, &mut writer, args.flags, &mut state)
+            {
+                handle_error(RatError::new(RatErrorType::NoFileFound, format!("{}", err)));
+            }
         } else {
-            println!("{}", line_to_print);
+            match decompress::open_reader(&path) {
+                Ok(reader) => {
+                    if let Err(err) =
+                        print_concatenated_files(reader, &mut writer, args.flags, &mut state)
+                    {
+                        handle_error(RatError::new(RatErrorType::NoFileFound, format!("{}", err)));
+                    }
+                }
+                Err(err) => {
+                    handle_error(RatError::new(RatErrorType::NoFileFound, format!("{}", err)))
+                }
+            }
         }
     }
 }
 
-fn enter_repl() {
-    let mut buffer = String::new();
-    let stdin = io::stdin();
+/// Streams `reader` one line at a time as raw bytes, applying the flag
+/// pipeline and writing straight to `writer`. Keeps the whole file out of
+/// memory and passes non-UTF-8 bytes through unchanged.
+fn print_concatenated_files<R: BufRead, W: Write>(
+    mut reader: R,
+    writer: &mut W,
+    flags: RatFlags,
+    state: &mut StreamState,
+) -> io::Result<()> {
+    let mut line = Vec::new();
 
     loop {
-        match stdin.read_line(&mut buffer) {
-            Ok(_) => {
-                println!("{}", buffer);
-                buffer.clear();
-            }
-            Err(err) => panic!("{}", err),
+        line.clear();
+        let bytes_read = reader.read_until(b'\n', &mut line)?;
+        if bytes_read == 0 {
+            break;
         }
+        let had_newline = line.last() == Some(&b'\n');
+        if had_newline {
+            line.pop();
+        }
+
+        print_line(&line, had_newline, writer, flags, state)?;
     }
+
+    Ok(())
 }
 
-fn choose_your_adventure(args: RatArgs) {
-    if args.paths.is_empty() {
-        enter_repl();
+fn print_line<W: Write>(
+    line: &[u8],
+    had_newline: bool,
+    writer: &mut W,
+    flags: RatFlags,
+    state: &mut StreamState,
+) -> io::Result<()> {
+    let mut line_to_print = if flags.show_nonprinting {
+        render_nonprinting(line)
     } else {
-        run(args)
+        line.to_vec()
+    };
+
+    if flags.show_tabs {
+        line_to_print = replace_tabs(&line_to_print);
+    }
+
+    if flags.show_ends {
+        line_to_print.push(b'$');
+    }
+
+    if flags.squeeze_blank {
+        if line_to_print.is_empty() && state.previous_line_empty {
+            return Ok(());
+        }
+        state.previous_line_empty = line_to_print.is_empty();
+    }
+
+    if flags.output_nums && !flags.number_nonblank {
+        write!(writer, "{}    ", state.line_count)?;
+        state.line_count += 1;
+    } else if !line.is_empty() && flags.number_nonblank {
+        write!(writer, "{}    ", state.line_count)?;
+        state.line_count += 1;
     }
+
+    writer.write_all(&line_to_print)?;
+    if had_newline {
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod print_concatenated_files_tests {
+    use super::{print_concatenated_files, RatFlags, StreamState};
+    use std::io::Cursor;
+
+    fn no_flags() -> RatFlags {
+        RatFlags {
+            output_nums: false,
+            squeeze_blank: false,
+            number_nonblank: false,
+            show_tabs: false,
+            show_ends: false,
+            show_nonprinting: false,
+        }
+    }
+
+    #[test]
+    fn passes_non_utf8_bytes_through_unchanged() {
+        let input = vec![b'a', 0xff, 0xfe, b'\n', b'b', 0x80, b'\n'];
+        let mut output = Vec::new();
+        let mut state = StreamState::new();
+
+        print_concatenated_files(
+            Cursor::new(input.clone()),
+            &mut output,
+            no_flags(),
+            &mut state,
+        )
+        .unwrap();
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn handles_a_final_line_with_no_trailing_newline() {
+        let input = b"first\nsecond".to_vec();
+        let mut output = Vec::new();
+        let mut state = StreamState::new();
+
+        print_concatenated_files(Cursor::new(input), &mut output, no_flags(), &mut state).unwrap();
+
+        assert_eq!(output, b"first\nsecond".to_vec());
+    }
+}
+
+/// Renders a byte the way GNU `cat -v` does: control chars as `^X`, DEL as
+/// `^?`, and high bytes as `M-` followed by the caret-rendering of the low
+/// 7 bits. Tab and newline are left to the caller (`-v` never touches them).
+fn render_nonprinting(line: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(line.len());
+    for &b in line {
+        match b {
+            b'\t' => out.push(b),
+            0..=31 => {
+                out.push(b'^');
+                out.push(b + 64);
+            }
+            127 => out.extend_from_slice(b"^?"),
+            128..=255 => {
+                out.extend_from_slice(b"M-");
+                out.extend_from_slice(&render_nonprinting(&[b & 0x7f]));
+            }
+            _ => out.push(b),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod render_nonprinting_tests {
+    use super::render_nonprinting;
+
+    #[test]
+    fn leaves_printable_ascii_and_tab_untouched() {
+        assert_eq!(render_nonprinting(b"hi\tthere"), b"hi\tthere".to_vec());
+    }
+
+    #[test]
+    fn renders_control_chars_with_caret_notation() {
+        assert_eq!(render_nonprinting(&[0x00]), b"^@".to_vec());
+        assert_eq!(render_nonprinting(&[0x01]), b"^A".to_vec());
+        assert_eq!(render_nonprinting(&[0x1f]), b"^_".to_vec());
+    }
+
+    #[test]
+    fn renders_del_as_caret_question_mark() {
+        assert_eq!(render_nonprinting(&[127]), b"^?".to_vec());
+    }
+
+    #[test]
+    fn renders_high_control_bytes_with_meta_caret_notation() {
+        assert_eq!(render_nonprinting(&[0x80]), b"M-^@".to_vec());
+        assert_eq!(render_nonprinting(&[0x9f]), b"M-^_".to_vec());
+    }
+
+    #[test]
+    fn renders_high_printable_bytes_with_meta_notation() {
+        assert_eq!(render_nonprinting(&[0xa0]), b"M- ".to_vec());
+        assert_eq!(render_nonprinting(&[0xfe]), b"M-~".to_vec());
+    }
+
+    #[test]
+    fn renders_0xff_as_meta_del() {
+        assert_eq!(render_nonprinting(&[0xff]), b"M-^?".to_vec());
+    }
+}
+
+fn replace_tabs(line: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(line.len());
+    for &b in line {
+        if b == b'\t' {
+            out.extend_from_slice(b"^I");
+        } else {
+            out.push(b);
+        }
+    }
+    out
 }
 
 fn display_help() {
@@ -192,8 +674,13 @@ fn display_version() {
 
 fn handle_error(error: RatError) {
     eprintln!("{}", error.message);
+    if let Some(suggestion) = &error.suggestion {
+        eprintln!("Did you mean '--{}'?", suggestion);
+    }
     match error.error {
-        RatErrorType::InvalidFlag => eprintln!("Try 'rat --help' for more information."),
+        RatErrorType::InvalidFlag | RatErrorType::AmbiguousFlag => {
+            eprintln!("Try 'rat --help' for more information.")
+        }
         _ => return,
     }
 }
@@ -203,6 +690,6 @@ fn main() {
 
     match args.error {
         Some(error) => handle_error(error),
-        None => choose_your_adventure(args),
+        None => run(args),
     }
 }